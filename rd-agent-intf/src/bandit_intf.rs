@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Arguments for the `mem-hog` bandit, serialized into the agent's JSON
+/// interface and parsed from the command line. `#[serde(default)]` keeps older
+/// on-disk configs loadable as new knobs are added.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanditMemHogArgs {
+    /// Target write bandwidth (size string or "<pct>%" of $IO_WBPS).
+    pub wbps: String,
+    /// Target read bandwidth (size string or "<pct>%" of $IO_RBPS).
+    pub rbps: String,
+    /// Number of reader threads sharing `rbps`.
+    pub nr_readers: usize,
+    /// Target compressibility of filled pages in `[0, 1]`.
+    pub comp: f64,
+    /// Not read by the mem-hog bandit: token accrual and loss are bounded by
+    /// `burst`.
+    pub max_debt: f64,
+    /// Reader access distribution: `uniform` or `zipf(<theta>)`.
+    pub access_dist: String,
+    /// Seconds between telemetry reports (clamped to a minimum of 1).
+    pub report_interval: u64,
+    /// Optional path to append telemetry report lines to.
+    pub report_path: Option<String>,
+    /// Target compression ratio (uncompressed/compressed) for filled pages;
+    /// `0` disables the mode and falls back to `comp`.
+    pub target_ratio: f64,
+    /// Percent of filled pages to zstd-verify for realized ratio.
+    pub verify_sample_pct: f64,
+    /// Arena backing: `anon` or `file(<path>)`.
+    pub backing: String,
+    /// In file mode, seconds between `msync`/`MADV_DONTNEED` cycles; `0`
+    /// disables. Ignored for anon backing.
+    pub sync_period_secs: u64,
+    /// Token-bucket burst capacity: a size string or "<pct>%" of the rate. Must
+    /// resolve to at least one page.
+    pub burst: String,
+}
+
+impl Default for BanditMemHogArgs {
+    fn default() -> Self {
+        Self {
+            wbps: "0".into(),
+            rbps: "0".into(),
+            nr_readers: 1,
+            comp: 0.0,
+            max_debt: 10.0,
+            access_dist: "uniform".into(),
+            report_interval: 5,
+            report_path: None,
+            target_ratio: 0.0,
+            verify_sample_pct: 0.0,
+            backing: "anon".into(),
+            sync_period_secs: 0,
+            burst: "1M".into(),
+        }
+    }
+}
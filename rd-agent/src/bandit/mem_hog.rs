@@ -1,15 +1,17 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use log::{debug, info, trace};
+use rand::Rng;
 use rd_agent_intf::BanditMemHogArgs;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread::spawn;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use util::anon_area::AnonArea;
 use util::*;
 
 const ANON_SIZE_CLICK: usize = 1 << 30;
-const MAX_WRITE: usize = 1 << 20;
 
 struct State {
     aa: AnonArea,
@@ -37,66 +39,451 @@ fn parse_bps(input: &str, base_env_key: &str) -> Result<usize> {
     }
 }
 
-struct DebtTracker {
-    debt: f64,
-    max_debt: f64,
+/// What the arena is backed by; file backing turns the writer's pages into
+/// dirty page-cache that the kernel writes back and reclaims differently from
+/// anonymous swap.
+enum Backing {
+    Anon,
+    File(String),
+}
+
+fn parse_backing(input: &str) -> Result<Backing> {
+    if input == "anon" {
+        Ok(Backing::Anon)
+    } else if let Some(path) = input.strip_prefix("file(").and_then(|s| s.strip_suffix(')')) {
+        Ok(Backing::File(path.to_string()))
+    } else {
+        Err(anyhow!("unknown backing {:?}", input))
+    }
+}
+
+/// How the reader distributes its accesses across its assigned page range.
+#[derive(Clone, Copy)]
+enum AccessDist {
+    /// Every page is equally likely - the historical behavior.
+    Uniform,
+    /// Concentrate on a small hot set following a Zipf distribution of the
+    /// given skew; higher `theta` means a hotter, smaller working set.
+    Zipf(f64),
+}
+
+fn parse_access_dist(input: &str) -> Result<AccessDist> {
+    if input == "uniform" {
+        Ok(AccessDist::Uniform)
+    } else if let Some(theta) = input
+        .strip_prefix("zipf(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        let theta = theta
+            .parse::<f64>()
+            .with_context(|| format!("failed to parse {:?}", input))?;
+        if !(0.0..1.0).contains(&theta) {
+            bail!("zipf theta {} must be in [0, 1)", theta);
+        }
+        Ok(AccessDist::Zipf(theta))
+    } else {
+        Err(anyhow!("unknown access_dist {:?}", input))
+    }
+}
+
+/// O(1) rejection-free Zipfian rank sampler following Gray et al.'s "Quickly
+/// Generating Billion-Record Synthetic Databases". The expensive `zeta_n`/`eta`
+/// terms are O(n) to recompute, so we quantize the requested range size down to
+/// a coarse bucket (top few significant bits) and only recompute when that
+/// bucket changes. The writer nudges `total_pages` upward on essentially every
+/// iteration, so raw equality would pay the O(n) cost per batch; bucketing keeps
+/// it genuinely O(1) amortized. Rounding *down* guarantees the cached `n` never
+/// exceeds the live range, so sampled ranks stay in bounds.
+struct ZipfSampler {
+    theta: f64,
+    n: usize,
+    zeta_n: f64,
+    zeta_2: f64,
+    alpha: f64,
+    eta: f64,
+}
+
+impl ZipfSampler {
+    fn new(theta: f64) -> Self {
+        Self {
+            theta,
+            n: 0,
+            zeta_n: 0.0,
+            zeta_2: 1.0 + 0.5f64.powf(theta),
+            alpha: 1.0 / (1.0 - theta),
+            eta: 0.0,
+        }
+    }
+
+    /// Quantize `n` down to a bucket so near-identical range sizes reuse the
+    /// same `zeta_n`/`eta`. Small ranges are kept exact so the `n==1`/`n==2`
+    /// edge cases are never perturbed; larger ones keep their top 4 significant
+    /// bits (under 12.5% relative coarsening) and round toward zero.
+    fn bucket(n: usize) -> usize {
+        if n <= 16 {
+            n
+        } else {
+            let shift = usize::BITS - n.leading_zeros() - 4;
+            (n >> shift) << shift
+        }
+    }
+
+    fn prepare(&mut self, n: usize) {
+        let bn = Self::bucket(n);
+        if bn == self.n {
+            return;
+        }
+        self.n = bn;
+        self.zeta_n = (1..=bn).map(|i| 1.0 / (i as f64).powf(self.theta)).sum();
+        self.eta = (1.0 - (2.0 / bn as f64).powf(1.0 - self.theta))
+            / (1.0 - self.zeta_2 / self.zeta_n);
+    }
+
+    /// Map a uniform draw `u` in `[0, 1)` to a rank in `0..n`.
+    fn sample(&self, u: f64) -> usize {
+        let uz = u * self.zeta_n;
+        let rank = if uz < 1.0 {
+            0
+        } else if uz < self.zeta_2 {
+            1
+        } else {
+            (self.n as f64 * (self.eta * u - self.eta + 1.0).powf(self.alpha)) as usize
+        };
+        rank.min(self.n - 1)
+    }
+}
+
+/// zstd level used for both the target-ratio model and the verification pass;
+/// kept fixed so the realized ratio is comparable run to run.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Fill `page` so that a block compressor reproduces a `ratio`-to-1 compression
+/// ratio: the first `round(PAGE_SIZE / ratio)` bytes carry CSPRNG output and the
+/// remainder is zeroed. The incompressible run is shifted to a deterministic
+/// per-page offset so the compressor cannot exploit a fixed alignment.
+fn fill_page_target_ratio(page: &mut [u64], page_idx: usize, ratio: f64) {
+    let incomp_bytes = ((page.len() * 8) as f64 / ratio).round() as usize;
+    let incomp_words = ((incomp_bytes + 7) / 8).min(page.len());
+    let max_off = page.len() - incomp_words;
+    let off = if max_off > 0 {
+        (page_idx.wrapping_mul(0x9E37_79B9_7F4A_7C15)) % (max_off + 1)
+    } else {
+        0
+    };
+    let mut rng = rand::thread_rng();
+    for w in page.iter_mut() {
+        *w = 0;
+    }
+    for w in page[off..off + incomp_words].iter_mut() {
+        *w = rng.gen();
+    }
+}
+
+/// Realized compression ratio (uncompressed / compressed) of a single filled
+/// page under `zstd` at [`ZSTD_LEVEL`].
+fn verify_page_ratio(page: &[u64]) -> f64 {
+    let bytes: Vec<u8> = page.iter().flat_map(|w| w.to_le_bytes()).collect();
+    match zstd::bulk::compress(&bytes, ZSTD_LEVEL) {
+        Ok(c) => bytes.len() as f64 / c.len().max(1) as f64,
+        Err(_) => 0.0,
+    }
+}
+
+/// Running mean/stddev of observed compression ratios from the verification
+/// sample.
+#[derive(Default)]
+struct VerifyStats {
+    n: u64,
+    sum: f64,
+    sqsum: f64,
+}
+
+impl VerifyStats {
+    fn record(&mut self, ratio: f64) {
+        self.n += 1;
+        self.sum += ratio;
+        self.sqsum += ratio * ratio;
+    }
+
+    fn mean(&self) -> f64 {
+        if self.n == 0 {
+            0.0
+        } else {
+            self.sum / self.n as f64
+        }
+    }
+
+    fn stddev(&self) -> f64 {
+        if self.n == 0 {
+            0.0
+        } else {
+            (self.sqsum / self.n as f64 - self.mean().powi(2)).max(0.0).sqrt()
+        }
+    }
+}
+
+const LAT_NR_BUCKETS: usize = 72;
+const LAT_BASE_NS: f64 = 100.0;
+const LAT_FACTOR: f64 = 1.3;
+
+/// Fixed log-spaced latency histogram stored as `AtomicU64` counts, so
+/// recording a sample is a single atomic increment and percentiles are only
+/// materialized at report time. Bucket `i` covers page-access durations around
+/// `LAT_BASE_NS * LAT_FACTOR^i` nanoseconds, spanning ~100ns to ~10s.
+struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: (0..LAT_NR_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    fn record(&self, dur: Duration) {
+        let ns = dur.as_nanos() as f64;
+        let idx = if ns <= LAT_BASE_NS {
+            0
+        } else {
+            (((ns / LAT_BASE_NS).ln() / LAT_FACTOR.ln()) as usize).min(LAT_NR_BUCKETS - 1)
+        };
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Representative latency at the `p` (0..1) quantile, in seconds.
+    fn percentile(&self, p: f64) -> f64 {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let target = (total as f64 * p).ceil() as u64;
+        let mut cum = 0u64;
+        for (idx, cnt) in counts.iter().enumerate() {
+            cum += cnt;
+            if cum >= target {
+                return LAT_BASE_NS * LAT_FACTOR.powi(idx as i32) / 1e9;
+            }
+        }
+        LAT_BASE_NS * LAT_FACTOR.powi((LAT_NR_BUCKETS - 1) as i32) / 1e9
+    }
+}
+
+/// Shared telemetry fed by every writer/reader thread and drained by a
+/// background reporter thread. Byte counters and latency histograms are updated
+/// lock-free; the compression-ratio verification sample is kept behind a `Mutex`.
+struct Telemetry {
+    write_bytes: AtomicU64,
+    read_bytes: AtomicU64,
+    write_lat: LatencyHistogram,
+    read_lat: LatencyHistogram,
+    target_ratio: Option<f64>,
+    verify: Mutex<VerifyStats>,
+}
+
+impl Telemetry {
+    fn new(target_ratio: Option<f64>) -> Self {
+        Self {
+            write_bytes: AtomicU64::new(0),
+            read_bytes: AtomicU64::new(0),
+            write_lat: LatencyHistogram::new(),
+            read_lat: LatencyHistogram::new(),
+            target_ratio,
+            verify: Mutex::new(VerifyStats::default()),
+        }
+    }
+
+    fn record_ratio(&self, ratio: f64) {
+        self.verify.lock().unwrap().record(ratio);
+    }
+
+    fn record_write(&self, bytes: usize, dur: Duration) {
+        self.write_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.write_lat.record(dur);
+    }
+
+    fn record_read(&self, bytes: usize, dur: Duration) {
+        self.read_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.read_lat.record(dur);
+    }
+
+    /// Format a one-line summary covering `elapsed` since the last snapshot,
+    /// updating `last_w`/`last_r` to the current byte totals.
+    fn report_line(&self, elapsed: f64, last_w: &mut u64, last_r: &mut u64) -> String {
+        let w = self.write_bytes.load(Ordering::Relaxed);
+        let r = self.read_bytes.load(Ordering::Relaxed);
+        let wbps = (w - *last_w) as f64 / elapsed.max(f64::MIN_POSITIVE);
+        let rbps = (r - *last_r) as f64 / elapsed.max(f64::MIN_POSITIVE);
+        *last_w = w;
+        *last_r = r;
+        let lat = |h: &LatencyHistogram| {
+            format!(
+                "p50={:.1}us p90={:.1}us p99={:.1}us p99.9={:.1}us",
+                h.percentile(0.50) * 1e6,
+                h.percentile(0.90) * 1e6,
+                h.percentile(0.99) * 1e6,
+                h.percentile(0.999) * 1e6,
+            )
+        };
+        let mut line = format!(
+            "wbps={} rbps={} write[{}] read[{}]",
+            format_size(wbps as u64 as usize),
+            format_size(rbps as u64 as usize),
+            lat(&self.write_lat),
+            lat(&self.read_lat),
+        );
+        if let Some(target) = self.target_ratio {
+            let v = self.verify.lock().unwrap();
+            if v.n > 0 {
+                line += &format!(
+                    " comp_ratio[target={:.2} mean={:.2} stddev={:.2} n={}]",
+                    target,
+                    v.mean(),
+                    v.stddev(),
+                    v.n,
+                );
+            }
+        }
+        line
+    }
+}
+
+/// Background thread: emit an achieved-throughput / latency-percentile line
+/// every `interval`, optionally appending to `path`, plus a final summary once
+/// the program is exiting.
+fn reporter(interval: Duration, path: Option<String>, tm: Arc<Telemetry>) {
+    let mut out = path.as_ref().map(|p| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(p)
+            .with_context(|| format!("failed to open report path {:?}", p))
+            .unwrap()
+    });
+    let start = Instant::now();
+    let mut last_tick = start;
+    let (mut last_w, mut last_r) = (0u64, 0u64);
+
+    while !prog_exiting() {
+        wait_prog_state(interval);
+        let now = Instant::now();
+        let elapsed = now.duration_since(last_tick).as_secs_f64();
+        last_tick = now;
+        let line = tm.report_line(elapsed, &mut last_w, &mut last_r);
+        info!("telemetry: {}", line);
+        if let Some(out) = out.as_mut() {
+            let _ = writeln!(out, "{:.3} {}", now.duration_since(start).as_secs_f64(), line);
+        }
+    }
+
+    let elapsed = Instant::now().duration_since(last_tick).as_secs_f64();
+    let line = tm.report_line(elapsed, &mut last_w, &mut last_r);
+    info!("telemetry: final {}", line);
+    if let Some(out) = out.as_mut() {
+        let _ = writeln!(out, "final {}", line);
+    }
+}
+
+fn parse_burst(input: &str, bps: usize) -> Result<f64> {
+    let burst = if let Some(pct) = input.strip_suffix('%') {
+        let pct = pct
+            .parse::<f64>()
+            .with_context(|| format!("failed to parse burst {:?}", input))?;
+        bps as f64 * pct / 100.0
+    } else {
+        parse_size(input)? as f64
+    };
+    if burst < *PAGE_SIZE as f64 {
+        bail!(
+            "burst {} must be at least one page ({} bytes)",
+            format_size(burst as usize),
+            *PAGE_SIZE
+        );
+    }
+    Ok(burst)
+}
+
+/// Classic token bucket: tokens (in bytes) accrue at `bps` up to `burst`
+/// capacity, and each page moved consumes `PAGE_SIZE` tokens. Tokens that would
+/// overflow `burst` are recorded as "loss" — demand that outran the bucket's
+/// capacity — mirroring the old debt-clamp accounting.
+struct TokenBucket {
+    bps: f64,
+    burst: f64,
+    tokens: f64,
     loss: f64,
     last_at: SystemTime,
 }
 
-impl DebtTracker {
-    fn new(max_debt: f64) -> Self {
+impl TokenBucket {
+    fn new(bps: usize, burst: f64) -> Self {
         Self {
-            debt: 0.0,
-            max_debt,
+            bps: bps as f64,
+            burst,
+            tokens: 0.0,
             loss: 0.0,
             last_at: SystemTime::now(),
         }
     }
 
-    fn update(&mut self) -> f64 {
+    fn refill(&mut self) {
         let now = SystemTime::now();
-        self.debt += match now.duration_since(self.last_at) {
+        let elapsed = match now.duration_since(self.last_at) {
             Ok(dur) => dur.as_secs_f64(),
             Err(_) => 0.0,
         };
         self.last_at = now;
 
-        if self.debt > self.max_debt {
-            self.loss += self.debt - self.max_debt;
+        self.tokens += elapsed * self.bps;
+        if self.tokens > self.burst {
+            self.loss += self.tokens - self.burst;
             debug!(
-                "debt={} max_debt={} loss={}",
-                self.debt, self.max_debt, self.loss
+                "tokens={} burst={} loss={}",
+                self.tokens, self.burst, self.loss
             );
-            self.debt = self.max_debt;
+            self.tokens = self.burst;
         }
-
-        self.debt
     }
 
-    fn pay(&mut self, amt: f64) {
-        self.debt = (self.debt - amt).max(0.0);
+    fn consume(&mut self, amt: f64) {
+        self.tokens = (self.tokens - amt).max(0.0);
     }
 }
 
-fn debt_bps_to_nr_pages_or_sleep(debt: f64, bps: usize) -> Option<usize> {
-    let bytes = (debt * bps as f64).round() as usize;
-    if bytes < *PAGE_SIZE {
-        let sleep_for = *PAGE_SIZE as f64 / bps as f64;
+/// Accrue tokens and either return how many whole pages the bucket currently
+/// affords (bounded by `burst`, which replaces the old hard `MAX_WRITE`
+/// ceiling) or, when a full page isn't yet available, sleep until it is.
+fn bucket_to_nr_pages_or_sleep(bucket: &mut TokenBucket) -> Option<usize> {
+    bucket.refill();
+    if bucket.tokens < *PAGE_SIZE as f64 {
+        let sleep_for = (*PAGE_SIZE as f64 - bucket.tokens) / bucket.bps;
         trace!("sleeping for {}", sleep_for);
         wait_prog_state(Duration::from_secs_f64(sleep_for));
         None
     } else {
-        Some(bytes.min(MAX_WRITE) / *PAGE_SIZE)
+        Some(bucket.tokens as usize / *PAGE_SIZE)
     }
 }
 
-fn writer(wbps: usize, max_debt: f64, state: Arc<RwLock<State>>) {
-    let mut debt_tracker = DebtTracker::new(max_debt);
+fn writer(
+    wbps: usize,
+    burst: f64,
+    target_ratio: Option<f64>,
+    verify_sample_pct: f64,
+    sync_period: Option<Duration>,
+    tm: Arc<Telemetry>,
+    state: Arc<RwLock<State>>,
+) {
+    let mut bucket = TokenBucket::new(wbps, burst);
+    let mut last_sync = Instant::now();
 
     while !prog_exiting() {
-        let debt = debt_tracker.update();
-        let nr_pages = match debt_bps_to_nr_pages_or_sleep(debt, wbps) {
+        let nr_pages = match bucket_to_nr_pages_or_sleep(&mut bucket) {
             Some(v) => v,
             None => continue,
         };
@@ -120,23 +507,53 @@ fn writer(wbps: usize, max_debt: f64, state: Arc<RwLock<State>>) {
         }
 
         trace!("filling {} pages {}-{}", nr_pages, start_page, end_page);
+        let mut rng = rand::thread_rng();
         for page_idx in start_page..end_page {
-            st.aa.fill_page_with_random(page_idx);
+            let at = Instant::now();
+            match target_ratio {
+                Some(ratio) => fill_page_target_ratio(st.aa.access_page(page_idx), page_idx, ratio),
+                None => st.aa.fill_page_with_random(page_idx),
+            }
+            tm.record_write(*PAGE_SIZE, at.elapsed());
+            if target_ratio.is_some() && rng.gen::<f64>() * 100.0 < verify_sample_pct {
+                tm.record_ratio(verify_page_ratio(st.aa.access_page(page_idx)));
+            }
         }
 
         st.wpage_pos.store(end_page, Ordering::Relaxed);
-        debt_tracker.pay((nr_pages * *PAGE_SIZE) as f64 / wbps as f64);
+        bucket.consume((nr_pages * *PAGE_SIZE) as f64);
+
+        // For file backing, periodically flush dirty pages and drop them so the
+        // demo can contrast clean vs. dirty page-cache reclaim; a no-op for anon.
+        if let Some(period) = sync_period {
+            if last_sync.elapsed() >= period {
+                st.aa.sync();
+                st.aa.dontneed();
+                last_sync = Instant::now();
+            }
+        }
+        drop(st);
     }
 }
 
-fn reader(range: (f64, f64), rbps: usize, max_debt: f64, state: Arc<RwLock<State>>) {
-    let mut debt_tracker = DebtTracker::new(max_debt);
+fn reader(
+    range: (f64, f64),
+    rbps: usize,
+    burst: f64,
+    access_dist: AccessDist,
+    tm: Arc<Telemetry>,
+    state: Arc<RwLock<State>>,
+) {
+    let mut bucket = TokenBucket::new(rbps, burst);
     let mut page_pos: usize = 0;
     let mut sum: u64 = 0;
+    let mut zipf = match access_dist {
+        AccessDist::Zipf(theta) => Some(ZipfSampler::new(theta)),
+        AccessDist::Uniform => None,
+    };
 
     while !prog_exiting() {
-        let debt = debt_tracker.update();
-        let nr_pages = match debt_bps_to_nr_pages_or_sleep(debt, rbps) {
+        let nr_pages = match bucket_to_nr_pages_or_sleep(&mut bucket) {
             Some(v) => v,
             None => continue,
         };
@@ -149,10 +566,23 @@ fn reader(range: (f64, f64), rbps: usize, max_debt: f64, state: Arc<RwLock<State
         );
         let nr_range_pages = page_range.1 - page_range.0;
         if nr_range_pages > 0 {
+            if let Some(zipf) = zipf.as_mut() {
+                zipf.prepare(nr_range_pages);
+            }
+            let mut rng = rand::thread_rng();
             for _ in 0..nr_pages {
-                let page: &mut [u64] = st.aa.access_page(page_range.0 + page_pos);
+                let idx = match zipf.as_ref() {
+                    Some(zipf) => zipf.sample(rng.gen::<f64>()),
+                    None => {
+                        let idx = page_pos;
+                        page_pos = (page_pos + 1) % nr_range_pages;
+                        idx
+                    }
+                };
+                let at = Instant::now();
+                let page: &mut [u64] = st.aa.access_page(page_range.0 + idx);
                 sum += page[0];
-                page_pos = (page_pos + 1) % nr_range_pages;
+                tm.record_read(*PAGE_SIZE, at.elapsed());
             }
             trace!(
                 "read {} pages from {}-{}, page_pos={}",
@@ -165,40 +595,82 @@ fn reader(range: (f64, f64), rbps: usize, max_debt: f64, state: Arc<RwLock<State
             trace!("no pages in the range, skipping {} pages", nr_pages);
         }
 
-        debt_tracker.pay((nr_pages * *PAGE_SIZE) as f64 / rbps as f64);
+        bucket.consume((nr_pages * *PAGE_SIZE) as f64);
     }
 }
 
 pub fn bandit_mem_hog(args: &BanditMemHogArgs) {
+    let backing = parse_backing(&args.backing).unwrap();
+    let aa = match &backing {
+        Backing::Anon => AnonArea::new(ANON_SIZE_CLICK, args.comp),
+        Backing::File(path) => AnonArea::new_file(path, ANON_SIZE_CLICK, args.comp),
+    };
     let state = Arc::new(RwLock::new(State {
-        aa: AnonArea::new(ANON_SIZE_CLICK, args.comp),
+        aa,
         wpage_pos: AtomicUsize::new(0),
     }));
 
     let wbps = parse_bps(&args.wbps, "IO_WBPS").unwrap();
     let rbps = parse_bps(&args.rbps, "IO_RBPS").unwrap();
+    let access_dist = parse_access_dist(&args.access_dist).unwrap();
 
     info!(
-        "Target wbps={} rbps={} readers={}",
+        "Target wbps={} rbps={} readers={} access_dist={}",
         format_size(wbps),
         format_size(rbps),
         args.nr_readers,
+        args.access_dist,
     );
 
+    let target_ratio = if args.target_ratio > 0.0 {
+        Some(args.target_ratio)
+    } else {
+        None
+    };
+    let tm = Arc::new(Telemetry::new(target_ratio));
+
     let mut jhs = vec![];
+    {
+        let interval = Duration::from_secs(args.report_interval.max(1));
+        let path = args.report_path.clone();
+        let tm = tm.clone();
+        jhs.push(spawn(move || reporter(interval, path, tm)));
+    }
     if wbps > 0 {
-        let max_debt = args.max_debt;
+        let burst = parse_burst(&args.burst, wbps).unwrap();
+        let verify_sample_pct = args.verify_sample_pct;
+        // The msync/MADV_DONTNEED cadence only makes sense against a file
+        // mapping; on anon backing DONTNEED would discard the writer's filled
+        // pages out from under the readers, so leave it disabled.
+        let sync_period = match (&backing, args.sync_period_secs) {
+            (Backing::File(_), secs) if secs > 0 => Some(Duration::from_secs(secs)),
+            _ => None,
+        };
+        let tm = tm.clone();
         let state_copy = state.clone();
-        jhs.push(spawn(move || writer(wbps, max_debt, state_copy)));
+        jhs.push(spawn(move || {
+            writer(
+                wbps,
+                burst,
+                target_ratio,
+                verify_sample_pct,
+                sync_period,
+                tm,
+                state_copy,
+            )
+        }));
     }
     let rbps = (rbps as f64 / args.nr_readers as f64).ceil() as usize;
     if rbps > 0 {
+        let burst = parse_burst(&args.burst, rbps).unwrap();
         for i in 0..args.nr_readers {
             let section = 1.0 / args.nr_readers as f64;
             let range = (i as f64 * section, (i + 1) as f64 * section);
-            let max_debt = args.max_debt;
+            let tm = tm.clone();
             let state_copy = state.clone();
-            jhs.push(spawn(move || reader(range, rbps, max_debt, state_copy)));
+            jhs.push(spawn(move || {
+                reader(range, rbps, burst, access_dist, tm, state_copy)
+            }));
         }
     }
 
@@ -206,3 +678,185 @@ pub fn bandit_mem_hog(args: &BanditMemHogArgs) {
         jh.join().unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tally the rank histogram for a uniform sweep of `u` over `[0, 1)`.
+    fn rank_histogram(theta: f64, n: usize, samples: usize) -> Vec<usize> {
+        let mut zipf = ZipfSampler::new(theta);
+        zipf.prepare(n);
+        let mut hist = vec![0usize; n];
+        for i in 0..samples {
+            let rank = zipf.sample(i as f64 / samples as f64);
+            assert!(rank < n, "rank {} out of range for n={}", rank, n);
+            hist[rank] += 1;
+        }
+        hist
+    }
+
+    #[test]
+    fn zipf_frequency_is_monotonically_decreasing() {
+        for &theta in &[0.5, 0.8, 0.99] {
+            for &n in &[8, 100, 1000] {
+                let hist = rank_histogram(theta, n, 1_000_000);
+                // Adjacent tail ranks can differ by less than one grid step of
+                // `u`, so allow one sample of quantization slack.
+                for w in hist.windows(2) {
+                    assert!(
+                        w[0] + 1 >= w[1],
+                        "theta={} n={}: frequency not decreasing ({} < {})",
+                        theta,
+                        n,
+                        w[0],
+                        w[1]
+                    );
+                }
+                assert!(hist[0] > hist[n - 1], "theta={} n={}: no hot set", theta, n);
+            }
+        }
+    }
+
+    #[test]
+    fn zipf_single_page_range() {
+        let mut zipf = ZipfSampler::new(0.99);
+        zipf.prepare(1);
+        for i in 0..1000 {
+            assert_eq!(zipf.sample(i as f64 / 1000.0), 0);
+        }
+    }
+
+    #[test]
+    fn zipf_two_page_range() {
+        let hist = rank_histogram(0.99, 2, 100_000);
+        assert!(hist[0] >= hist[1]);
+        assert_eq!(hist[0] + hist[1], 100_000);
+    }
+
+    #[test]
+    fn latency_percentile_lands_in_recorded_bucket() {
+        let h = LatencyHistogram::new();
+        // All samples in one bucket: every percentile resolves to that bucket's
+        // representative latency, which is within LAT_FACTOR of the input.
+        for _ in 0..1000 {
+            h.record(Duration::from_micros(1));
+        }
+        for &p in &[0.5, 0.9, 0.99, 0.999] {
+            let got = h.percentile(p);
+            assert!(
+                got >= 1e-6 / LAT_FACTOR && got <= 1e-6 * LAT_FACTOR,
+                "p{} = {} not within a bucket of 1us",
+                p,
+                got
+            );
+        }
+        // An empty histogram reports zero rather than panicking.
+        assert_eq!(LatencyHistogram::new().percentile(0.5), 0.0);
+    }
+
+    #[test]
+    fn latency_percentile_orders_across_buckets() {
+        let h = LatencyHistogram::new();
+        for _ in 0..90 {
+            h.record(Duration::from_micros(1));
+        }
+        for _ in 0..10 {
+            h.record(Duration::from_millis(100));
+        }
+        // The tail (p99) must sit well above the bulk (p50).
+        assert!(h.percentile(0.99) > h.percentile(0.50) * 100.0);
+    }
+
+    #[test]
+    fn telemetry_aggregates_bytes_and_rate() {
+        let tm = Telemetry::new(None);
+        tm.record_write(4096, Duration::from_micros(1));
+        tm.record_write(4096, Duration::from_micros(1));
+        tm.record_read(8192, Duration::from_micros(2));
+        assert_eq!(tm.write_bytes.load(Ordering::Relaxed), 8192);
+        assert_eq!(tm.read_bytes.load(Ordering::Relaxed), 8192);
+
+        // Over a 2s window the 8192 write bytes report as 4096 B/s.
+        let (mut last_w, mut last_r) = (0u64, 0u64);
+        let line = tm.report_line(2.0, &mut last_w, &mut last_r);
+        assert!(line.contains(&format!("wbps={}", format_size(4096))), "{}", line);
+        assert_eq!(last_w, 8192);
+        assert_eq!(last_r, 8192);
+
+        // A following window with no new traffic reports zero.
+        let line = tm.report_line(1.0, &mut last_w, &mut last_r);
+        assert!(line.contains(&format!("wbps={}", format_size(0))), "{}", line);
+    }
+
+    #[test]
+    fn target_ratio_fill_round_trips_and_stays_in_bounds() {
+        let words = *PAGE_SIZE / std::mem::size_of::<u64>();
+        // Span the extremes: barely compressible through highly compressible.
+        for &ratio in &[1.0, 1.2, 4.0, 50.0, 512.0] {
+            let mut page = vec![0u64; words];
+            fill_page_with_random_guard(&mut page);
+            fill_page_target_ratio(&mut page, 42, ratio);
+
+            // The incompressible run plus its per-page offset must fit the page.
+            let incomp_bytes = ((page.len() * 8) as f64 / ratio).round() as usize;
+            let incomp_words = ((incomp_bytes + 7) / 8).min(page.len());
+            assert!(incomp_words <= page.len());
+
+            // zstd should reproduce roughly the requested ratio (loose bound:
+            // headers and entropy coding keep it from being exact).
+            let got = verify_page_ratio(&page);
+            assert!(got >= 1.0, "ratio {}: got {} < 1", ratio, got);
+            assert!(
+                got >= ratio / 3.0 && got <= ratio * 3.0 + 4.0,
+                "ratio {}: realized {} too far from target",
+                ratio,
+                got
+            );
+        }
+    }
+
+    // Poison the buffer first so the test fails if fill leaves stale bytes.
+    fn fill_page_with_random_guard(page: &mut [u64]) {
+        for (i, w) in page.iter_mut().enumerate() {
+            *w = 0xDEAD_BEEF_0000_0000 | i as u64;
+        }
+    }
+
+    #[test]
+    fn token_bucket_consume_floors_at_zero() {
+        let mut bucket = TokenBucket::new(1 << 20, (4 * *PAGE_SIZE) as f64);
+        bucket.tokens = (3 * *PAGE_SIZE) as f64;
+        bucket.consume(*PAGE_SIZE as f64);
+        assert_eq!(bucket.tokens, (2 * *PAGE_SIZE) as f64);
+        // Over-consuming never goes negative.
+        bucket.consume((10 * *PAGE_SIZE) as f64);
+        assert_eq!(bucket.tokens, 0.0);
+    }
+
+    #[test]
+    fn token_bucket_refill_clamps_to_burst_and_charges_loss() {
+        let bps = 1 << 20; // 1 MiB/s
+        let burst = (2 * *PAGE_SIZE) as f64;
+        let mut bucket = TokenBucket::new(bps, burst);
+        // Pretend a full second elapsed: accrual (1 MiB) far exceeds burst.
+        bucket.last_at = SystemTime::now() - Duration::from_secs(1);
+        bucket.refill();
+        assert_eq!(bucket.tokens, burst, "tokens must clamp to burst");
+        assert!(
+            bucket.loss >= bps as f64 - burst - *PAGE_SIZE as f64,
+            "overflow beyond burst must be charged as loss (loss={})",
+            bucket.loss
+        );
+    }
+
+    #[test]
+    fn zipf_bucketing_keeps_small_ranges_exact() {
+        for n in 1..=16 {
+            assert_eq!(ZipfSampler::bucket(n), n);
+        }
+        // Larger ranges round down, never up, so the cached n stays in bounds.
+        assert!(ZipfSampler::bucket(1000) <= 1000);
+        assert!(ZipfSampler::bucket(1 << 20) <= 1 << 20);
+    }
+}
@@ -0,0 +1,172 @@
+use rand::RngCore;
+use std::fs::{File, OpenOptions};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+
+use super::PAGE_SIZE;
+
+/// A contiguous arena used by the mem-hog bandit to generate memory pressure.
+///
+/// With anonymous backing the pages live in an `MAP_PRIVATE | MAP_ANONYMOUS`
+/// mapping and exercise swap / anon reclaim. With file backing the same pages
+/// are an `MAP_SHARED` mapping of a growing file, so writes become dirty
+/// page-cache that the kernel writes back and reclaims as file pages instead of
+/// swapping them. The writer/reader logic is backing-agnostic: it only sees
+/// [`size`](Self::size), [`resize`](Self::resize), [`access_page`](Self::access_page),
+/// [`fill_page_with_random`](Self::fill_page_with_random), and
+/// [`sync`](Self::sync)/[`dontneed`](Self::dontneed), which are no-ops for
+/// anonymous backing.
+pub struct AnonArea {
+    base: *mut libc::c_void,
+    size: usize,
+    comp: f64,
+    file: Option<File>,
+}
+
+// The mapping is only mutated through `&mut self` (resize) or page-at-a-time by
+// a single logical owner per page, so sharing the handle across threads behind
+// an `RwLock` is sound.
+unsafe impl Send for AnonArea {}
+unsafe impl Sync for AnonArea {}
+
+impl AnonArea {
+    /// Anonymous arena of at least `size` bytes.
+    pub fn new(size: usize, comp: f64) -> Self {
+        let mut aa = AnonArea {
+            base: ptr::null_mut(),
+            size: 0,
+            comp,
+            file: None,
+        };
+        aa.resize(size);
+        aa
+    }
+
+    /// File-backed arena of at least `size` bytes. `path` is created (or
+    /// truncated if it exists) and `ftruncate`d to the initial size.
+    pub fn new_file<P: AsRef<Path>>(path: P, size: usize, comp: f64) -> Self {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_ref())
+            .unwrap_or_else(|e| panic!("failed to open {:?}: {}", path.as_ref(), e));
+        let mut aa = AnonArea {
+            base: ptr::null_mut(),
+            size: 0,
+            comp,
+            file: Some(file),
+        };
+        aa.resize(size);
+        aa
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Grow (or shrink) the arena to at least `new_size` bytes, rounded up to a
+    /// page. File backing `ftruncate`s the file first; both backings then
+    /// `mremap` the existing mapping (or `mmap` on first use) so page contents
+    /// are preserved across the move.
+    pub fn resize(&mut self, new_size: usize) {
+        let new_size = (new_size + *PAGE_SIZE - 1) / *PAGE_SIZE * *PAGE_SIZE;
+        if new_size == self.size {
+            return;
+        }
+
+        let base = unsafe {
+            if let Some(file) = self.file.as_ref() {
+                if libc::ftruncate(file.as_raw_fd(), new_size as libc::off_t) != 0 {
+                    panic!("ftruncate failed: {}", std::io::Error::last_os_error());
+                }
+                if self.base.is_null() {
+                    libc::mmap(
+                        ptr::null_mut(),
+                        new_size,
+                        libc::PROT_READ | libc::PROT_WRITE,
+                        libc::MAP_SHARED,
+                        file.as_raw_fd(),
+                        0,
+                    )
+                } else {
+                    libc::mremap(self.base, self.size, new_size, libc::MREMAP_MAYMOVE)
+                }
+            } else if self.base.is_null() {
+                libc::mmap(
+                    ptr::null_mut(),
+                    new_size,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                    -1,
+                    0,
+                )
+            } else {
+                libc::mremap(self.base, self.size, new_size, libc::MREMAP_MAYMOVE)
+            }
+        };
+
+        if base == libc::MAP_FAILED {
+            panic!("mmap/mremap failed: {}", std::io::Error::last_os_error());
+        }
+        self.base = base;
+        self.size = new_size;
+    }
+
+    /// A mutable view of page `idx` as a slice of `T`. The caller is
+    /// responsible for not aliasing the same page from multiple threads.
+    #[allow(clippy::mut_from_ref)]
+    pub fn access_page<T>(&self, idx: usize) -> &mut [T] {
+        let off = idx * *PAGE_SIZE;
+        assert!(off + *PAGE_SIZE <= self.size, "page {} out of range", idx);
+        unsafe {
+            let ptr = (self.base as *mut u8).add(off) as *mut T;
+            slice::from_raw_parts_mut(ptr, *PAGE_SIZE / std::mem::size_of::<T>())
+        }
+    }
+
+    /// Fill page `idx` with enough CSPRNG bytes to leave roughly `1 - comp` of
+    /// the page incompressible, zeroing the rest.
+    pub fn fill_page_with_random(&self, idx: usize) {
+        let page: &mut [u8] = self.access_page(idx);
+        let incomp = ((page.len() as f64) * (1.0 - self.comp)) as usize;
+        rand::thread_rng().fill_bytes(&mut page[..incomp]);
+        for b in page[incomp..].iter_mut() {
+            *b = 0;
+        }
+    }
+
+    /// Flush dirty pages back to the backing file. A no-op for anonymous
+    /// backing, which has nothing to write back.
+    pub fn sync(&self) {
+        if self.file.is_some() && !self.base.is_null() {
+            unsafe {
+                libc::msync(self.base, self.size, libc::MS_SYNC);
+            }
+        }
+    }
+
+    /// Drop the arena's resident pages with `MADV_DONTNEED` so the next access
+    /// faults them back in from the page cache. A no-op for anonymous backing,
+    /// where it would instead discard live data.
+    pub fn dontneed(&self) {
+        if self.file.is_some() && !self.base.is_null() {
+            unsafe {
+                libc::madvise(self.base, self.size, libc::MADV_DONTNEED);
+            }
+        }
+    }
+}
+
+impl Drop for AnonArea {
+    fn drop(&mut self) {
+        if !self.base.is_null() {
+            unsafe {
+                libc::munmap(self.base, self.size);
+            }
+        }
+    }
+}